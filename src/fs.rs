@@ -2,7 +2,9 @@ use std::{
     fmt,
     future::{poll_fn, Future},
     io::{self, Seek, SeekFrom, Write},
+    path::Path,
     pin::Pin,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
 };
 
@@ -13,95 +15,371 @@ use crate::{
     futures::{ready, AsyncRead, AsyncSeek, AsyncWrite},
 };
 
+#[cfg(feature = "io-uring")]
+mod io_uring;
+#[cfg(feature = "io-uring")]
+pub use io_uring::File;
+
+#[cfg(not(feature = "io-uring"))]
 enum State {
-    Idle(Option<std::fs::File>),
+    Idle,
+    Closed,
     InRead {
         reader: Option<piper::Reader>,
-        task: Task<(io::Result<()>, std::fs::File)>,
+        task: Task<io::Result<()>>,
     },
     InWrite {
         writer: Option<piper::Writer>,
-        task: Task<(io::Result<()>, std::fs::File)>,
+        task: Task<io::Result<()>>,
     },
     InSeek {
-        task: Task<(SeekFrom, io::Result<u64>, std::fs::File)>,
+        task: Task<io::Result<u64>>,
+    },
+    /// The read/write task in `InRead`/`InWrite` has finished and `res` holds its result, but the
+    /// std file's cursor still needs to be moved back to `pos` before `Idle` can be published;
+    /// that re-seek runs on `blocking::Executor` like every other OS round trip here, rather
+    /// than inline on whatever thread is polling.
+    Reseeking {
+        res: io::Result<()>,
+        task: Task<io::Result<()>>,
     },
 }
 
+#[cfg(not(feature = "io-uring"))]
 impl fmt::Debug for State {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            State::Idle(_) => write!(f, "Idle"),
+            State::Idle => write!(f, "Idle"),
+            State::Closed => write!(f, "Closed"),
             State::InRead { .. } => write!(f, "InRead"),
             State::InWrite { .. } => write!(f, "InWrite"),
             State::InSeek { .. } => write!(f, "InSeek"),
+            State::Reseeking { .. } => write!(f, "Reseeking"),
         }
     }
 }
 
-pub struct File {
+/// The state machine plus the logical cursor, guarded together by one mutex so that the
+/// `&self` I/O helpers (which may run concurrently with a pending read/write/seek) always see
+/// a consistent pair.
+#[cfg(not(feature = "io-uring"))]
+struct Shared {
     state: State,
+    /// The logical cursor position, as seen by the caller. The background read/write tasks
+    /// move the underlying `std::fs::File`'s cursor away from this (far ahead of it with
+    /// read-ahead buffering, behind it while written bytes still sit in the pipe), so this field is the only reliable source of truth between calls;
+    /// the invariant is that the std file's cursor equals `pos` whenever `state` is `Idle`.
+    pos: u64,
+}
+
+/// An open file, backed by a blocking-thread pool.
+///
+/// The `std::fs::File` handle is held behind an `Arc` so it can be cloned into a spawned
+/// blocking task without taking exclusive ownership away from `self`; this is what lets
+/// [`File::read_at`]/[`File::write_at`] run without waiting on a streaming read or write to
+/// finish first. The other `&self` helpers (`sync_all`, `metadata`, ...) first drive any
+/// pending read/write/seek back to `Idle`.
+#[cfg(not(feature = "io-uring"))]
+pub struct File {
+    file: Arc<std::fs::File>,
+    shared: Mutex<Shared>,
 }
 
+#[cfg(not(feature = "io-uring"))]
 impl<T> From<T> for File
 where
     std::fs::File: From<T>,
 {
     fn from(file: T) -> Self {
+        let mut file: std::fs::File = file.into();
+        let pos = file.stream_position().unwrap_or(0);
         Self {
-            state: State::Idle(Some(file.into())),
+            file: Arc::new(file),
+            shared: Mutex::new(Shared {
+                state: State::Idle,
+                pos,
+            }),
         }
     }
 }
 
+#[cfg(not(feature = "io-uring"))]
 impl File {
-    fn poll_stop(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    /// Opens a file in read-only mode.
+    ///
+    /// See [`std::fs::File::open`] for details.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let file = blocking::Executor::spawn(async move { std::fs::File::open(path) }).await?;
+        Ok(File::from(file))
+    }
+
+    /// Opens a file in write-only mode, creating it if it doesn't exist and truncating it if it
+    /// does.
+    ///
+    /// See [`std::fs::File::create`] for details.
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let file = blocking::Executor::spawn(async move { std::fs::File::create(path) }).await?;
+        Ok(File::from(file))
+    }
+
+    fn poll_stop(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         loop {
-            match &mut self.state {
+            let mut shared = self.shared.lock().expect("state mutex poisoned");
+            match &mut shared.state {
                 State::InRead { reader, task } => {
                     drop(reader.take());
-                    let (res, io) = ready!(Pin::new(task).poll(cx));
-                    self.state = State::Idle(Some(io));
-                    res?;
+                    let res = ready!(Pin::new(task).poll(cx));
+                    // The re-seek is just another OS round trip, so it runs on
+                    // `blocking::Executor` like every other one here rather than inline on
+                    // whatever thread happens to be polling.
+                    let file = Arc::clone(&self.file);
+                    let pos = shared.pos;
+                    let task = blocking::Executor::spawn(async move {
+                        file.as_ref().seek(SeekFrom::Start(pos)).map(|_| ())
+                    });
+                    shared.state = State::Reseeking { res, task };
+                    continue;
                 }
                 State::InWrite { writer, task } => {
                     drop(writer.take());
-                    let (res, io) = ready!(Pin::new(task).poll(cx));
-                    self.state = State::Idle(Some(io));
-                    res?;
+                    let res = ready!(Pin::new(task).poll(cx));
+                    let file = Arc::clone(&self.file);
+                    let pos = shared.pos;
+                    let task = blocking::Executor::spawn(async move {
+                        file.as_ref().seek(SeekFrom::Start(pos)).map(|_| ())
+                    });
+                    shared.state = State::Reseeking { res, task };
+                    continue;
                 }
                 State::InSeek { task } => {
-                    let (_, res, file) = ready!(Pin::new(task).poll(cx));
-                    self.state = State::Idle(Some(file));
+                    let res = ready!(Pin::new(task).poll(cx));
+                    shared.state = State::Idle;
+                    drop(shared);
                     res?;
                 }
-                State::Idle(_) => return Poll::Ready(Ok(())),
+                State::Reseeking { res, task } => {
+                    let seek_res = ready!(Pin::new(task).poll(cx));
+                    // Take `res` out so we can move `shared.state` to `Idle` below without
+                    // fighting the borrow checker over the in-place enum fields.
+                    let res = std::mem::replace(res, Ok(()));
+                    shared.state = State::Idle;
+                    drop(shared);
+                    // The state goes back to `Idle` whichever of the two results failed. If the
+                    // re-seek itself failed, the std file's cursor is not at `pos`, and that
+                    // error is returned ahead of the read/write task's own result.
+                    seek_res?;
+                    res?;
+                }
+                // A file that's already idle or closed has nothing left to drive; in
+                // particular, a second `close()`/`flush()` call after the first succeeded is a
+                // no-op, not an error, matching `std::fs::File`'s own tolerance of redundant
+                // cleanup calls.
+                State::Idle | State::Closed => return Poll::Ready(Ok(())),
             }
         }
     }
+
+    /// Drives the file back to `State::Idle`, then runs `f` against a clone of the shared
+    /// `std::fs::File` handle on `blocking::Executor`.
+    async fn dispatch<F, T>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&std::fs::File) -> io::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        poll_fn(|cx| self.poll_stop(cx)).await?;
+
+        let file = Arc::clone(&self.file);
+        blocking::Executor::spawn(async move { f(&file) }).await
+    }
+
+    /// Attempts to sync all OS-internal file content and metadata to disk.
+    ///
+    /// See [`std::fs::File::sync_all`] for details.
+    pub async fn sync_all(&self) -> io::Result<()> {
+        self.dispatch(|file| file.sync_all()).await
+    }
+
+    /// Attempts to sync file data to disk, without flushing metadata that isn't required to
+    /// access the data (`fdatasync`).
+    ///
+    /// See [`std::fs::File::sync_data`] for details.
+    pub async fn sync_data(&self) -> io::Result<()> {
+        self.dispatch(|file| file.sync_data()).await
+    }
+
+    /// Truncates or extends the underlying file to `size` bytes.
+    ///
+    /// See [`std::fs::File::set_len`] for details.
+    pub async fn set_len(&self, size: u64) -> io::Result<()> {
+        self.dispatch(move |file| file.set_len(size)).await
+    }
+
+    /// Queries metadata about the underlying file.
+    ///
+    /// See [`std::fs::File::metadata`] for details.
+    pub async fn metadata(&self) -> io::Result<std::fs::Metadata> {
+        self.dispatch(|file| file.metadata()).await
+    }
+
+    /// Changes the permissions on the underlying file.
+    ///
+    /// See [`std::fs::File::set_permissions`] for details.
+    pub async fn set_permissions(&self, perm: std::fs::Permissions) -> io::Result<()> {
+        self.dispatch(move |file| file.set_permissions(perm)).await
+    }
+
+    /// Reads at most `buf.len()` bytes starting at `offset`, without moving the file's logical
+    /// cursor (`pos`) and without spinning up the read-ahead pipe used by `poll_read`.
+    ///
+    /// This is the right primitive for random-access workloads, such as serving byte-range
+    /// requests, where a streaming read's large buffered fill is pure overhead.
+    pub async fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let file = Arc::clone(&self.file);
+        let len = buf.len();
+        let (res, data) = blocking::Executor::spawn(async move {
+            let mut data = vec![0u8; len];
+            let res = read_at(&file, &mut data, offset);
+            (res, data)
+        })
+        .await;
+        let n = res?;
+        buf[..n].copy_from_slice(&data[..n]);
+        Ok(n)
+    }
+
+    /// Writes at most `buf.len()` bytes starting at `offset`, without moving the file's logical
+    /// cursor (`pos`) and without spinning up the write-coalescing pipe used by `poll_write`.
+    pub async fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let file = Arc::clone(&self.file);
+        let data = buf.to_vec();
+        blocking::Executor::spawn(async move { write_at(&file, &data, offset) }).await
+    }
+}
+
+#[cfg(all(not(feature = "io-uring"), unix))]
+fn read_at(file: &std::fs::File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(all(not(feature = "io-uring"), windows))]
+fn read_at(file: &std::fs::File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(all(not(feature = "io-uring"), unix))]
+fn write_at(file: &std::fs::File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::write_at(file, buf, offset)
+}
+
+#[cfg(all(not(feature = "io-uring"), windows))]
+fn write_at(file: &std::fs::File, buf: &[u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_write(file, buf, offset)
+}
+
+/// Options and flags which can be used to configure how a file is opened.
+///
+/// Mirrors [`std::fs::OpenOptions`], with `open` dispatched through `blocking::Executor` and
+/// resolving to an async [`File`].
+#[derive(Clone, Debug)]
+pub struct OpenOptions(std::fs::OpenOptions);
+
+impl OpenOptions {
+    /// Creates a blank set of options ready for configuration.
+    ///
+    /// See [`std::fs::OpenOptions::new`] for details.
+    pub fn new() -> OpenOptions {
+        OpenOptions(std::fs::OpenOptions::new())
+    }
+
+    /// Sets the option for read access.
+    pub fn read(&mut self, read: bool) -> &mut OpenOptions {
+        self.0.read(read);
+        self
+    }
+
+    /// Sets the option for write access.
+    pub fn write(&mut self, write: bool) -> &mut OpenOptions {
+        self.0.write(write);
+        self
+    }
+
+    /// Sets the option for the append mode.
+    pub fn append(&mut self, append: bool) -> &mut OpenOptions {
+        self.0.append(append);
+        self
+    }
+
+    /// Sets the option for truncating a previous file.
+    pub fn truncate(&mut self, truncate: bool) -> &mut OpenOptions {
+        self.0.truncate(truncate);
+        self
+    }
+
+    /// Sets the option to create a new file, or open it if it already exists.
+    pub fn create(&mut self, create: bool) -> &mut OpenOptions {
+        self.0.create(create);
+        self
+    }
+
+    /// Sets the option to create a new file, failing if it already exists.
+    pub fn create_new(&mut self, create_new: bool) -> &mut OpenOptions {
+        self.0.create_new(create_new);
+        self
+    }
+
+    /// Sets the mode bits that a new file will be created with.
+    ///
+    /// See [`std::os::unix::fs::OpenOptionsExt::mode`] for details.
+    #[cfg(unix)]
+    pub fn mode(&mut self, mode: u32) -> &mut OpenOptions {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        self.0.mode(mode);
+        self
+    }
+
+    /// Opens a file at `path` with the options specified by `self`, running the blocking
+    /// `std::fs::OpenOptions::open` call on `blocking::Executor`.
+    pub async fn open(&self, path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let options = self.0.clone();
+        let file = blocking::Executor::spawn(async move { options.open(path) }).await?;
+        Ok(File::from(file))
+    }
 }
 
+impl Default for OpenOptions {
+    fn default() -> OpenOptions {
+        OpenOptions::new()
+    }
+}
+
+#[cfg(not(feature = "io-uring"))]
 impl AsyncRead for File {
     fn poll_read(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
         loop {
-            match &mut self.state {
-                State::Idle(file) => {
-                    let mut file = file.take().expect("file must be existed in idle state");
+            let mut shared = self.shared.lock().expect("state mutex poisoned");
+            match &mut shared.state {
+                State::Idle => {
+                    let file = Arc::clone(&self.file);
                     let (reader, mut writer) = piper::pipe(8 * 1024 * 1024);
                     let task = blocking::Executor::spawn(async move {
+                        let mut file = &*file;
                         loop {
                             match poll_fn(|cx| writer.poll_fill(cx, &mut file)).await {
-                                Ok(0) => return (Ok(()), file),
+                                Ok(0) => return Ok(()),
                                 Ok(_) => {}
-                                Err(err) => return (Err(err), file),
+                                Err(err) => return Err(err),
                             }
                         }
                     });
-                    self.state = State::InRead {
+                    shared.state = State::InRead {
                         reader: Some(reader),
                         task,
                     };
@@ -113,63 +391,84 @@ impl AsyncRead for File {
                         .poll_drain(cx, buf))?;
 
                     if n == 0 {
-                        let (res, io) = ready!(Pin::new(task).poll(cx));
-                        self.state = State::Idle(Some(io));
+                        let res = ready!(Pin::new(task).poll(cx));
+                        shared.state = State::Idle;
                         res?;
                     }
 
+                    shared.pos += n as u64;
                     return Poll::Ready(Ok(n));
                 }
-                _ => ready!(self.poll_stop(cx))?,
+                State::Closed => return Poll::Ready(Err(io::Error::other("file used after close"))),
+                _ => {
+                    drop(shared);
+                    ready!(self.poll_stop(cx))?;
+                }
             }
         }
     }
 }
 
+#[cfg(not(feature = "io-uring"))]
 impl AsyncWrite for File {
     fn poll_write(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
         loop {
-            match &mut self.state {
-                State::Idle(file) => {
-                    let mut file = file.take().expect("file must be existed in idle state");
+            let mut shared = self.shared.lock().expect("state mutex poisoned");
+            match &mut shared.state {
+                State::Idle => {
+                    let file = Arc::clone(&self.file);
                     let (mut reader, writer) = piper::pipe(8 * 1024 * 1024);
                     let task = blocking::Executor::spawn(async move {
+                        let mut file = &*file;
                         loop {
                             match poll_fn(|cx| reader.poll_drain(cx, &mut file)).await {
-                                Ok(0) => return (file.flush(), file),
+                                Ok(0) => return file.flush(),
                                 Ok(_) => {}
                                 Err(err) => {
                                     file.flush().ok();
-                                    return (Err(err), file);
+                                    return Err(err);
                                 }
                             }
                         }
                     });
-                    self.state = State::InWrite {
+                    shared.state = State::InWrite {
                         writer: Some(writer),
                         task,
                     };
                 }
                 State::InWrite { writer, .. } => {
-                    return writer
+                    let n = ready!(writer
                         .as_mut()
                         .expect("writer must be had")
-                        .poll_fill(cx, buf)
+                        .poll_fill(cx, buf))?;
+                    shared.pos += n as u64;
+                    return Poll::Ready(Ok(n));
+                }
+                State::Closed => return Poll::Ready(Err(io::Error::other("file used after close"))),
+                _ => {
+                    drop(shared);
+                    ready!(self.poll_stop(cx))?;
                 }
-                _ => ready!(self.poll_stop(cx))?,
             }
         }
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
         loop {
-            match &mut self.state {
-                State::Idle(_) => return Poll::Ready(Ok(())),
-                State::InRead { .. } | State::InWrite { .. } | State::InSeek { .. } => {
+            let shared = self.shared.lock().expect("state mutex poisoned");
+            match &shared.state {
+                // See the matching arm in `poll_stop`: a second `flush()` after `close()` is a
+                // no-op, not an error.
+                State::Idle | State::Closed => return Poll::Ready(Ok(())),
+                State::InRead { .. }
+                | State::InWrite { .. }
+                | State::InSeek { .. }
+                | State::Reseeking { .. } => {
+                    drop(shared);
                     ready!(self.poll_stop(cx))?;
                 }
             }
@@ -177,38 +476,47 @@ impl AsyncWrite for File {
     }
 
     fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
-        ready!(Pin::new(&mut self).poll_flush(cx))?;
-        self.state = State::Idle(None);
+        ready!(Pin::new(&mut *self).poll_flush(cx))?;
+        self.shared.lock().expect("state mutex poisoned").state = State::Closed;
         Poll::Ready(Ok(()))
     }
 }
 
+#[cfg(not(feature = "io-uring"))]
 impl AsyncSeek for File {
     fn poll_seek(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         pos: std::io::SeekFrom,
     ) -> Poll<io::Result<u64>> {
         loop {
-            match &mut self.state {
-                State::Idle(file) => {
-                    let mut file = file.take().expect("file must be existed in idle state");
-                    let task = blocking::Executor::spawn(async move {
-                        let res = file.seek(pos);
-                        (pos, res, file)
-                    });
-                    self.state = State::InSeek { task };
+            let mut shared = self.shared.lock().expect("state mutex poisoned");
+            match &mut shared.state {
+                State::Idle => {
+                    let file = Arc::clone(&self.file);
+                    // `pos` already tracks the logical cursor, and the Idle invariant keeps the
+                    // std file's cursor equal to it, so `Start`/`Current` can be resolved without
+                    // a round trip to the OS; only `End` needs the actual file size.
+                    let target = match pos {
+                        SeekFrom::Start(n) => SeekFrom::Start(n),
+                        SeekFrom::Current(n) => SeekFrom::Start((shared.pos as i64 + n) as u64),
+                        SeekFrom::End(_) => pos,
+                    };
+                    let task = blocking::Executor::spawn(async move { (&*file).seek(target) });
+                    shared.state = State::InSeek { task };
                 }
                 State::InSeek { task } => {
-                    let (original_pos, res, io) = ready!(Pin::new(task).poll(cx));
-                    self.state = State::Idle(Some(io));
+                    let res = ready!(Pin::new(task).poll(cx));
+                    shared.state = State::Idle;
                     let current = res?;
-
-                    if original_pos == pos {
-                        return Poll::Ready(Ok(current));
-                    }
+                    shared.pos = current;
+                    return Poll::Ready(Ok(current));
+                }
+                State::Closed => return Poll::Ready(Err(io::Error::other("file used after close"))),
+                _ => {
+                    drop(shared);
+                    ready!(self.poll_stop(cx))?;
                 }
-                _ => ready!(self.poll_stop(cx))?,
             }
         }
     }
@@ -239,4 +547,153 @@ mod tests {
                 file.close().await.unwrap();
             });
     }
+
+    #[test]
+    fn open_create_and_open_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::create(&path).await.unwrap();
+                file.write_all(b"hello").await.unwrap();
+                file.close().await.unwrap();
+
+                let mut file = super::File::open(&path).await.unwrap();
+                let mut buf = String::new();
+                file.read_to_string(&mut buf).await.unwrap();
+                assert_eq!(buf, "hello");
+                file.close().await.unwrap();
+
+                let mut file = super::OpenOptions::new()
+                    .append(true)
+                    .open(&path)
+                    .await
+                    .unwrap();
+                file.write_all(b" world").await.unwrap();
+                file.close().await.unwrap();
+
+                let mut file = super::File::open(&path).await.unwrap();
+                let mut buf = String::new();
+                file.read_to_string(&mut buf).await.unwrap();
+                assert_eq!(buf, "hello world");
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn sync_and_set_len() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"hello").await.unwrap();
+                file.sync_all().await.unwrap();
+                file.sync_data().await.unwrap();
+
+                file.set_len(2).await.unwrap();
+                let metadata = file.metadata().await.unwrap();
+                assert_eq!(metadata.len(), 2);
+
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn seek_after_partial_read_is_not_corrupted_by_read_ahead() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"0123456789").await.unwrap();
+                file.seek(std::io::SeekFrom::Start(0)).await.unwrap();
+
+                // Only consume part of what the read-ahead pipe fills in, then rely on
+                // `SeekFrom::Current` to reflect the logical position, not the OS cursor.
+                let mut buf = [0; 3];
+                file.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf, b"012");
+
+                let pos = file.seek(std::io::SeekFrom::Current(0)).await.unwrap();
+                assert_eq!(pos, 3);
+
+                let mut rest = Vec::new();
+                file.read_to_end(&mut rest).await.unwrap();
+                assert_eq!(&rest, b"3456789");
+
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn metadata_is_callable_through_a_shared_reference() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"hello").await.unwrap();
+
+                // `sync_all`/`metadata` take `&self`, so they're reachable without exclusive
+                // access to `file` even while other code might hold a shared reference too.
+                let file_ref: &super::File = &file;
+                file_ref.sync_all().await.unwrap();
+                let metadata = file_ref.metadata().await.unwrap();
+                assert_eq!(metadata.len(), 5);
+
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn read_at_and_write_at_leave_the_cursor_untouched() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"0123456789").await.unwrap();
+                file.seek(std::io::SeekFrom::Start(2)).await.unwrap();
+
+                file.write_at(b"XY", 5).await.unwrap();
+
+                let mut buf = [0; 4];
+                let n = file.read_at(&mut buf, 4).await.unwrap();
+                assert_eq!(n, 4);
+                assert_eq!(&buf, b"4XY7");
+
+                // Neither positioned call should have moved the logical cursor set above.
+                let pos = file.seek(std::io::SeekFrom::Current(0)).await.unwrap();
+                assert_eq!(pos, 2);
+
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn close_and_flush_are_idempotent() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"hello").await.unwrap();
+                file.close().await.unwrap();
+
+                // A second `close()`/`flush()` after the first succeeded is a no-op, matching
+                // `std::fs::File`'s own tolerance of redundant cleanup calls, not an error.
+                file.close().await.unwrap();
+                file.flush().await.unwrap();
+            });
+    }
 }
@@ -0,0 +1,609 @@
+//! An alternative `File` backed directly by an io_uring submission queue instead of
+//! `blocking::Executor`. Enabled with the `io-uring` feature; the public API and the
+//! `AsyncRead`/`AsyncWrite`/`AsyncSeek` impls are identical to the blocking-thread `File` in
+//! the parent module, so downstream code does not need to know which backend is active.
+//!
+//! Unlike the pipe-based backend, every read/write here is a positioned `pread`/`pwrite`
+//! submitted with an explicit offset, so there is no OS cursor to drift out of sync with the
+//! logical position: `pos` is simply advanced locally and `SeekFrom::Current`/`SeekFrom::Start`
+//! never touch the ring at all. The state machine and cursor are guarded by the same kind of
+//! `Shared` mutex the default backend uses, so the `&self` helpers below (`sync_all`,
+//! `metadata`, ...) can drain a pending streaming read/write via `poll_stop` before running,
+//! exactly like `File::dispatch` does in the parent module.
+//!
+//! Every in-flight SQE owns its buffer (if any) for as long as the kernel might still touch it:
+//! the buffer lives in `Ring::buffers`, keyed by a `user_data` id, until `Ring::drive` observes
+//! the matching completion, at which point it's moved into `Ring::completed` for the waiting
+//! poller to claim — never dropped by `drive` itself, so a read's data can't vanish before its
+//! caller reads it back out. `Drop for File` blocks until every submission it still owns has
+//! completed, so a cancelled read/write can never leave the kernel writing into memory Rust has
+//! already freed.
+
+use std::{
+    collections::HashMap,
+    future::{poll_fn, Future},
+    io::{self, Seek},
+    os::unix::io::AsRawFd,
+    path::Path,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll, Waker},
+};
+
+use async_task::Task;
+use io_uring::{opcode, squeue, types, IoUring};
+
+use crate::futures::{ready, AsyncRead, AsyncSeek, AsyncWrite};
+
+const RING_ENTRIES: u32 = 32;
+
+enum State {
+    Idle,
+    Reading { id: u64 },
+    Writing { id: u64 },
+    /// Only used for `SeekFrom::End`, which needs the file's actual size; `Start`/`Current` are
+    /// resolved locally against `pos` and never enter this state.
+    Seeking { task: Task<io::Result<u64>> },
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            State::Idle => write!(f, "Idle"),
+            State::Reading { .. } => write!(f, "Reading"),
+            State::Writing { .. } => write!(f, "Writing"),
+            State::Seeking { .. } => write!(f, "Seeking"),
+        }
+    }
+}
+
+/// The state machine plus the logical cursor, guarded together by one mutex — mirrors
+/// `Shared` in the parent module so the `&self` helpers can observe and drain both atomically.
+struct Shared {
+    state: State,
+    pos: u64,
+}
+
+/// The io_uring ring plus the bookkeeping needed to match completions back to the submission
+/// that produced them, since more than one operation (a streaming read/write and a concurrent
+/// `read_at`/`write_at`/`sync_all` call) can be in flight against the same ring at once.
+struct Ring {
+    ring: IoUring,
+    next_id: u64,
+    /// Number of SQEs submitted but not yet observed as completed in `drive`. `Drop for File`
+    /// blocks until this reaches zero before letting `buffers` (and the ring itself) go away.
+    outstanding: u64,
+    /// Results observed by `drive`, together with any buffer that was kept alive for the op,
+    /// waiting for the poller to claim them. Moved here out of `buffers` by `drive` itself, so
+    /// nothing is ever removed from `buffers` a second time by the claiming poll.
+    completed: HashMap<u64, (i32, Option<Vec<u8>>)>,
+    /// Wakers registered by a pending poll, to be woken once its id shows up in `completed`.
+    wakers: HashMap<u64, Waker>,
+    /// Buffers kept alive for the kernel's benefit until `drive` sees their completion.
+    buffers: HashMap<u64, Vec<u8>>,
+}
+
+impl Ring {
+    fn new() -> io::Result<Ring> {
+        Ok(Ring {
+            ring: IoUring::new(RING_ENTRIES)?,
+            next_id: 1,
+            outstanding: 0,
+            completed: HashMap::new(),
+            wakers: HashMap::new(),
+            buffers: HashMap::new(),
+        })
+    }
+
+    /// Drains every completion currently available, stashing results (and any buffer the op
+    /// owned) and waking whoever is waiting on them. This is the only place a buffer is ever
+    /// taken out of `buffers` — the claiming poll reads it back out of `completed` instead, so
+    /// there is exactly one removal per id, not two.
+    fn drive(&mut self) {
+        while let Some(cqe) = self.ring.completion().next() {
+            let id = cqe.user_data();
+            let buf = self.buffers.remove(&id);
+            self.completed.insert(id, (cqe.result(), buf));
+            self.outstanding = self.outstanding.saturating_sub(1);
+            if let Some(waker) = self.wakers.remove(&id) {
+                waker.wake();
+            }
+        }
+    }
+}
+
+pub struct File {
+    file: std::fs::File,
+    ring: Mutex<Ring>,
+    shared: Mutex<Shared>,
+}
+
+impl<T> From<T> for File
+where
+    std::fs::File: From<T>,
+{
+    fn from(file: T) -> Self {
+        let mut file: std::fs::File = file.into();
+        let pos = file.stream_position().unwrap_or(0);
+        Self {
+            file,
+            ring: Mutex::new(Ring::new().expect("failed to create io_uring")),
+            shared: Mutex::new(Shared {
+                state: State::Idle,
+                pos,
+            }),
+        }
+    }
+}
+
+impl File {
+    /// Opens a file in read-only mode.
+    ///
+    /// See [`std::fs::File::open`] for details.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let file =
+            crate::blocking::Executor::spawn(async move { std::fs::File::open(path) }).await?;
+        Ok(File::from(file))
+    }
+
+    /// Opens a file in write-only mode, creating it if it doesn't exist and truncating it if it
+    /// does.
+    ///
+    /// See [`std::fs::File::create`] for details.
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let file =
+            crate::blocking::Executor::spawn(async move { std::fs::File::create(path) }).await?;
+        Ok(File::from(file))
+    }
+
+    /// Submits `entry_fn(id)`, stashing `buf` (if any) until `Ring::drive` observes its
+    /// completion, and returns the id to poll for that completion with [`File::poll_ring`].
+    fn submit(
+        &self,
+        buf: Option<Vec<u8>>,
+        entry_fn: impl FnOnce(u64) -> squeue::Entry,
+    ) -> io::Result<u64> {
+        let mut ring = self.ring.lock().expect("io_uring mutex poisoned");
+        let id = ring.next_id;
+        ring.next_id += 1;
+        let entry = entry_fn(id);
+        if let Some(buf) = buf {
+            ring.buffers.insert(id, buf);
+        }
+        // Safety: the memory `entry` points into (if any) is owned by `ring.buffers` until
+        // `Ring::drive` observes the matching completion, which only happens once the kernel
+        // is done reading from or writing into it.
+        let pushed = unsafe { ring.ring.submission().push(&entry) };
+        if pushed.is_err() {
+            ring.buffers.remove(&id);
+            return Err(io::Error::other("io_uring submission queue full"));
+        }
+        ring.ring.submit()?;
+        ring.outstanding += 1;
+        Ok(id)
+    }
+
+    /// Non-blockingly checks whether `id`'s completion has arrived, registering `cx`'s waker
+    /// if not.
+    fn poll_ring(&self, id: u64, cx: &mut Context<'_>) -> Poll<(i32, Option<Vec<u8>>)> {
+        let mut ring = self.ring.lock().expect("io_uring mutex poisoned");
+        ring.drive();
+        if let Some(result) = ring.completed.remove(&id) {
+            Poll::Ready(result)
+        } else {
+            ring.wakers.insert(id, cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    fn submit_read(&self, pos: u64, len: usize) -> io::Result<State> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let mut data = vec![0u8; len];
+        let ptr = data.as_mut_ptr();
+        let id = self.submit(Some(data), move |id| {
+            opcode::Read::new(fd, ptr, len as _)
+                .offset(pos)
+                .build()
+                .user_data(id)
+        })?;
+        Ok(State::Reading { id })
+    }
+
+    fn submit_write(&self, pos: u64, buf: &[u8]) -> io::Result<State> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let mut data = buf.to_vec();
+        let ptr = data.as_mut_ptr();
+        let len = data.len();
+        let id = self.submit(Some(data), move |id| {
+            opcode::Write::new(fd, ptr, len as _)
+                .offset(pos)
+                .build()
+                .user_data(id)
+        })?;
+        Ok(State::Writing { id })
+    }
+
+    /// Drives whatever is currently in flight to completion, propagating its result and
+    /// advancing `pos` for reads and writes. Unlike the streaming `poll_read`/`poll_write`, this
+    /// takes `&self` so the `&self` helpers below can drain a pending operation before running.
+    fn poll_stop(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let mut shared = self.shared.lock().expect("state mutex poisoned");
+            match shared.state {
+                State::Idle => return Poll::Ready(Ok(())),
+                State::Reading { id } => {
+                    let (res, _buf) = ready!(self.poll_ring(id, cx));
+                    shared.state = State::Idle;
+                    if res < 0 {
+                        return Poll::Ready(Err(io::Error::from_raw_os_error(-res)));
+                    }
+                    shared.pos += res as u64;
+                }
+                State::Writing { id } => {
+                    let (res, _buf) = ready!(self.poll_ring(id, cx));
+                    shared.state = State::Idle;
+                    if res < 0 {
+                        return Poll::Ready(Err(io::Error::from_raw_os_error(-res)));
+                    }
+                    shared.pos += res as u64;
+                }
+                State::Seeking { ref mut task } => {
+                    let target = ready!(Pin::new(task).poll(cx))?;
+                    shared.state = State::Idle;
+                    shared.pos = target;
+                }
+            }
+        }
+    }
+
+    /// Drains any in-flight streaming read/write, then runs `f` against a clone of the file
+    /// handle on `blocking::Executor` — mirrors the default backend's `dispatch`.
+    async fn dispatch<F, T>(&self, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&std::fs::File) -> io::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        poll_fn(|cx| self.poll_stop(cx)).await?;
+        let file = self.file.try_clone()?;
+        crate::blocking::Executor::spawn(async move { f(&file) }).await
+    }
+
+    /// Attempts to sync all OS-internal file content and metadata to disk via an `Fsync` SQE,
+    /// after waiting for any in-flight streaming read/write to finish.
+    ///
+    /// See [`std::fs::File::sync_all`] for details.
+    pub async fn sync_all(&self) -> io::Result<()> {
+        poll_fn(|cx| self.poll_stop(cx)).await?;
+        let fd = types::Fd(self.file.as_raw_fd());
+        let id = self.submit(None, move |id| opcode::Fsync::new(fd).build().user_data(id))?;
+        let (res, _) = poll_fn(|cx| self.poll_ring(id, cx)).await;
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        Ok(())
+    }
+
+    /// Attempts to sync file data to disk, without flushing metadata that isn't required to
+    /// access the data (`fdatasync`), after waiting for any in-flight streaming read/write to
+    /// finish.
+    ///
+    /// See [`std::fs::File::sync_data`] for details.
+    pub async fn sync_data(&self) -> io::Result<()> {
+        poll_fn(|cx| self.poll_stop(cx)).await?;
+        let fd = types::Fd(self.file.as_raw_fd());
+        let id = self.submit(None, move |id| {
+            opcode::Fsync::new(fd)
+                .flags(types::FsyncFlags::DATASYNC)
+                .build()
+                .user_data(id)
+        })?;
+        let (res, _) = poll_fn(|cx| self.poll_ring(id, cx)).await;
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        Ok(())
+    }
+
+    /// Truncates or extends the underlying file to `size` bytes.
+    ///
+    /// `ftruncate` has no widely-supported io_uring opcode, so this falls back to the blocking
+    /// thread pool, same as the default backend.
+    pub async fn set_len(&self, size: u64) -> io::Result<()> {
+        self.dispatch(move |file| file.set_len(size)).await
+    }
+
+    /// Queries metadata about the underlying file.
+    ///
+    /// `statx` is left to the blocking thread pool for the same reason as [`File::set_len`].
+    pub async fn metadata(&self) -> io::Result<std::fs::Metadata> {
+        self.dispatch(|file| file.metadata()).await
+    }
+
+    /// Changes the permissions on the underlying file.
+    pub async fn set_permissions(&self, perm: std::fs::Permissions) -> io::Result<()> {
+        self.dispatch(move |file| file.set_permissions(perm)).await
+    }
+
+    /// Reads at most `buf.len()` bytes starting at `offset`, without moving the file's logical
+    /// cursor and independent of any streaming read/write in flight through the
+    /// `AsyncRead`/`AsyncWrite` impls.
+    pub async fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let len = buf.len();
+        let mut data = vec![0u8; len];
+        let ptr = data.as_mut_ptr();
+        let id = self.submit(Some(data), move |id| {
+            opcode::Read::new(fd, ptr, len as _)
+                .offset(offset)
+                .build()
+                .user_data(id)
+        })?;
+        let (res, data) = poll_fn(|cx| self.poll_ring(id, cx)).await;
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        let data = data.expect("buffer must be present for a completed read");
+        let n = res as usize;
+        buf[..n].copy_from_slice(&data[..n]);
+        Ok(n)
+    }
+
+    /// Writes at most `buf.len()` bytes starting at `offset`, without moving the file's logical
+    /// cursor and independent of any streaming read/write in flight through the
+    /// `AsyncRead`/`AsyncWrite` impls.
+    pub async fn write_at(&self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let data = buf.to_vec();
+        let ptr = data.as_ptr();
+        let len = data.len();
+        let id = self.submit(Some(data), move |id| {
+            opcode::Write::new(fd, ptr, len as _)
+                .offset(offset)
+                .build()
+                .user_data(id)
+        })?;
+        let (res, _buf) = poll_fn(|cx| self.poll_ring(id, cx)).await;
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+        Ok(res as usize)
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        let mut ring = self.ring.lock().expect("io_uring mutex poisoned");
+        while ring.outstanding > 0 {
+            ring.drive();
+            if ring.outstanding == 0 {
+                break;
+            }
+            // The kernel may still be reading from or writing into a buffer this `File`
+            // owns; block until at least one more completion arrives so it's safe to free
+            // everything once this function returns.
+            let _ = ring.ring.submit_and_wait(1);
+        }
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            let mut shared = this.shared.lock().expect("state mutex poisoned");
+            match shared.state {
+                State::Idle => {
+                    let pos = shared.pos;
+                    shared.state = this.submit_read(pos, buf.len())?;
+                }
+                State::Reading { id } => {
+                    let (res, data) = ready!(this.poll_ring(id, cx));
+                    shared.state = State::Idle;
+                    if res < 0 {
+                        return Poll::Ready(Err(io::Error::from_raw_os_error(-res)));
+                    }
+                    let data = data.expect("buffer must be present for a completed read");
+                    let n = res as usize;
+                    buf[..n].copy_from_slice(&data[..n]);
+                    shared.pos += n as u64;
+                    return Poll::Ready(Ok(n));
+                }
+                State::Writing { .. } | State::Seeking { .. } => {
+                    drop(shared);
+                    ready!(this.poll_stop(cx))?;
+                }
+            }
+        }
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            let mut shared = this.shared.lock().expect("state mutex poisoned");
+            match shared.state {
+                State::Idle => {
+                    let pos = shared.pos;
+                    shared.state = this.submit_write(pos, buf)?;
+                }
+                State::Writing { id } => {
+                    let (res, _buf) = ready!(this.poll_ring(id, cx));
+                    shared.state = State::Idle;
+                    if res < 0 {
+                        return Poll::Ready(Err(io::Error::from_raw_os_error(-res)));
+                    }
+                    let n = res as usize;
+                    shared.pos += n as u64;
+                    return Poll::Ready(Ok(n));
+                }
+                State::Reading { .. } | State::Seeking { .. } => {
+                    drop(shared);
+                    ready!(this.poll_stop(cx))?;
+                }
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_stop(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(Pin::new(&mut *self).poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for File {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: io::SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        loop {
+            let mut shared = this.shared.lock().expect("state mutex poisoned");
+            match &mut shared.state {
+                State::Idle => {
+                    // `Start`/`Current` are pure arithmetic against the logical cursor; only
+                    // `End` needs the actual file size, which (like `set_len` and `metadata`)
+                    // is dispatched to the blocking thread pool rather than called directly
+                    // here.
+                    match pos {
+                        io::SeekFrom::Start(n) => {
+                            shared.pos = n;
+                            return Poll::Ready(Ok(n));
+                        }
+                        io::SeekFrom::Current(n) => {
+                            let target = (shared.pos as i64 + n) as u64;
+                            shared.pos = target;
+                            return Poll::Ready(Ok(target));
+                        }
+                        io::SeekFrom::End(n) => {
+                            let file = this.file.try_clone()?;
+                            let task = crate::blocking::Executor::spawn(async move {
+                                let len = file.metadata()?.len();
+                                Ok((len as i64 + n) as u64)
+                            });
+                            shared.state = State::Seeking { task };
+                        }
+                    }
+                }
+                State::Seeking { task } => {
+                    let target = ready!(Pin::new(task).poll(cx))?;
+                    shared.state = State::Idle;
+                    shared.pos = target;
+                    return Poll::Ready(Ok(target));
+                }
+                State::Reading { .. } | State::Writing { .. } => {
+                    drop(shared);
+                    ready!(this.poll_stop(cx))?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempfile;
+
+    use crate::{
+        futures::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+        Executor,
+    };
+
+    #[test]
+    fn open_read_and_write() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"hello").await.unwrap();
+                file.seek(std::io::SeekFrom::Start(0)).await.unwrap();
+                let mut buf = [0; 5];
+                file.read_exact(&mut buf).await.unwrap();
+                assert_eq!(&buf, b"hello");
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn seek_from_end_is_dispatched_to_the_blocking_pool() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"0123456789").await.unwrap();
+                let pos = file.seek(std::io::SeekFrom::End(-3)).await.unwrap();
+                assert_eq!(pos, 7);
+
+                let mut rest = Vec::new();
+                file.read_to_end(&mut rest).await.unwrap();
+                assert_eq!(&rest, b"789");
+
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn sync_and_set_len() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"hello").await.unwrap();
+                file.sync_all().await.unwrap();
+                file.sync_data().await.unwrap();
+
+                file.set_len(2).await.unwrap();
+                let metadata = file.metadata().await.unwrap();
+                assert_eq!(metadata.len(), 2);
+
+                file.close().await.unwrap();
+            });
+    }
+
+    #[test]
+    fn read_at_and_write_at_leave_the_cursor_untouched() {
+        Executor::builder()
+            .worker_num(1)
+            .build()
+            .unwrap()
+            .block_on(async {
+                let mut file = super::File::from(tempfile().unwrap());
+                file.write_all(b"0123456789").await.unwrap();
+                file.seek(std::io::SeekFrom::Start(2)).await.unwrap();
+
+                file.write_at(b"XY", 5).await.unwrap();
+
+                let mut buf = [0; 4];
+                let n = file.read_at(&mut buf, 4).await.unwrap();
+                assert_eq!(n, 4);
+                assert_eq!(&buf, b"4XY7");
+
+                // Neither positioned call should have moved the logical cursor set above.
+                let pos = file.seek(std::io::SeekFrom::Current(0)).await.unwrap();
+                assert_eq!(pos, 2);
+
+                file.close().await.unwrap();
+            });
+    }
+}